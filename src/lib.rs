@@ -2,55 +2,49 @@ mod parser;
 mod tokenizer;
 mod vm;
 
-use std::io::{Read, Write, stdin, stdout};
+use std::io::{stdin, stdout};
 
 use parser::parse;
 use tokenizer::tokenize;
-use vm::VM;
+use vm::{EofPolicy, VM};
 
 use crate::vm::compile;
 
+/// Compile and run the program at the path given as the first CLI argument.
+///
+/// The program source comes from a file rather than stdin so that stdin is free for
+/// the program's own `,` (Read) instructions — reading source and program input from
+/// the same stream would exhaust it before the program ever got to run.
 pub fn repl() {
-    let mut machine = VM::new();
-    let mut line = String::new();
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: ccbf <path-to-program.bf> [--eof-keep]");
+        return;
+    };
 
-    loop {
-        prompt();
-
-        if let Some(input) = read(&mut line) {
-            let tokens = tokenize(&input);
-            let parse_result = parse(&tokens);
-
-            match parse_result {
-                Ok(parsed) => {
-                    let bytecode = compile(&parsed);
-                    machine.eval(&bytecode)
-                }
-                Err(error) => {
-                    println!("Parse error: {}", error);
-                    continue;
-                }
-            }
-        } else {
-            println!();
-            break;
-        }
-    }
-}
-
-fn read(line: &mut String) -> Option<String> {
-    line.clear();
-    match stdin().read_to_string(line) {
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
         Err(e) => {
             dbg!(e);
-            None
+            return;
         }
-        Ok(0) => None,
-        Ok(_) => Some(line.to_string()),
+    };
+
+    let tokens = tokenize(&source);
+    match parse(&tokens) {
+        Ok(parsed) => {
+            let bytecode = compile(&parsed);
+            let mut machine =
+                VM::with_io(Box::new(stdin()), Box::new(stdout())).with_eof_policy(eof_policy());
+            machine.eval(&bytecode);
+        }
+        Err(error) => println!("Parse error: {}", error),
     }
 }
 
-fn prompt() {
-    print!("CCBF> ");
-    stdout().flush().unwrap();
+fn eof_policy() -> EofPolicy {
+    if std::env::args().any(|arg| arg == "--eof-keep") {
+        EofPolicy::LeaveUnchanged
+    } else {
+        EofPolicy::SetZero
+    }
 }
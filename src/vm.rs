@@ -1,3 +1,5 @@
+use std::io::{self, Read as IoRead, Write, stdin, stdout};
+
 use crate::parser::Statement;
 
 const MEMORY_CELLS: usize = 30000;
@@ -6,6 +8,20 @@ pub struct VM {
     i_ptr: usize,
     mem_ptr: usize,
     memory: [u8; MEMORY_CELLS],
+    input: Box<dyn IoRead>,
+    output: Box<dyn Write>,
+    eof_policy: EofPolicy,
+}
+
+/// What a `,` should do to the current cell once `input` runs out of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EofPolicy {
+    /// Leave the current cell as-is.
+    LeaveUnchanged,
+    /// Reset the current cell to 0 (the default). This is what lets `,[.,]`-style cat
+    /// loops terminate on EOF instead of spinning forever on the last byte read.
+    #[default]
+    SetZero,
 }
 
 #[derive(Debug, PartialEq)]
@@ -13,6 +29,7 @@ pub enum Instruction {
     Increment(i32),
     Shift(i32),
     Print,
+    Read,
     JumpIfZero(usize),
     JumpIfNotZero(usize),
 }
@@ -27,6 +44,7 @@ enum CompilerState {
 pub enum RuntimeError {
     NegativeRegister,
     NoMoreCells,
+    IoError(io::ErrorKind),
 }
 
 impl Default for VM {
@@ -35,11 +53,29 @@ impl Default for VM {
             i_ptr: 0,
             mem_ptr: 0,
             memory: [0; MEMORY_CELLS],
+            input: Box::new(stdin()),
+            output: Box::new(stdout()),
+            eof_policy: EofPolicy::default(),
         }
     }
 }
 
 impl VM {
+    /// Build a `VM` with explicit I/O, e.g. for embedding or testing `,`/`.` without
+    /// touching the real stdin/stdout.
+    pub fn with_io(input: Box<dyn IoRead>, output: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            output,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_eof_policy(mut self, eof_policy: EofPolicy) -> Self {
+        self.eof_policy = eof_policy;
+        self
+    }
+
     pub fn eval(&mut self, expr: &[Instruction]) {
         while let Some(instruction) = expr.get(self.i_ptr) {
             match self.eval_one(instruction) {
@@ -76,7 +112,20 @@ impl VM {
                 }
             }
             Instruction::Print => {
-                print!("{}", *cell as char);
+                self.output
+                    .write_all(&[*cell])
+                    .map_err(|e| RuntimeError::IoError(e.kind()))?;
+            }
+            Instruction::Read => {
+                let mut buf = [0u8; 1];
+                match self.input.read(&mut buf) {
+                    Ok(0) => match self.eof_policy {
+                        EofPolicy::LeaveUnchanged => {}
+                        EofPolicy::SetZero => *cell = 0,
+                    },
+                    Ok(_) => *cell = buf[0],
+                    Err(e) => return Err(RuntimeError::IoError(e.kind())),
+                }
             }
             Instruction::JumpIfZero(target) if *cell == 0 => {
                 return Ok(*target);
@@ -129,11 +178,11 @@ pub fn compile(instructions: &[Statement]) -> Vec<Instruction> {
                     Statement::Decrement => state = CompilerState::Increment(-1),
 
                     Statement::Print => bytecode.push(Instruction::Print),
+                    Statement::Read => bytecode.push(Instruction::Read),
                     Statement::JumpIfZero(n) => bytecode.push(Instruction::JumpIfZero((*n).into())),
                     Statement::JumpIfNotZero(n) => {
                         bytecode.push(Instruction::JumpIfNotZero((*n).into()))
                     }
-                    _ => todo!("{:?} not implemented", instruction),
                 }
             }
         }
@@ -165,6 +214,23 @@ fn resolve_jumps(bytecode: &mut [Instruction]) -> () {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
 
     #[test]
     fn compile_multiple_increments() {
@@ -228,6 +294,13 @@ mod tests {
         assert_eq!(got, vec![Instruction::Increment(1), Instruction::Print]);
     }
 
+    #[test]
+    fn compile_with_read() {
+        let got = compile(&vec![Statement::Read, Statement::Print]);
+
+        assert_eq!(got, vec![Instruction::Read, Instruction::Print]);
+    }
+
     #[test]
     fn nested_jump_expressions() {
         let got = compile(&vec![
@@ -267,6 +340,7 @@ mod tests {
             i_ptr: 0,
             mem_ptr: 29999,
             memory: [0; MEMORY_CELLS],
+            ..VM::default()
         };
         vm.eval_one(&Instruction::Shift(1))
             .expect_err("Expected operation to fail");
@@ -278,4 +352,75 @@ mod tests {
         vm.eval_one(&Instruction::Shift(-1))
             .expect_err("Expected operation to fail");
     }
+
+    #[test]
+    fn print_writes_current_cell_to_output() {
+        let buffer = SharedBuffer::default();
+        let mut vm = VM {
+            output: Box::new(buffer.clone()),
+            ..VM::default()
+        };
+        vm.eval_one(&Instruction::Increment(65)).unwrap();
+        vm.eval_one(&Instruction::Print).unwrap();
+
+        assert_eq!(buffer.0.borrow().as_slice(), b"A");
+    }
+
+    #[test]
+    fn read_sets_current_cell_from_input() {
+        let mut vm = VM {
+            input: Box::new(Cursor::new(vec![65u8])),
+            ..VM::default()
+        };
+        vm.eval_one(&Instruction::Read).unwrap();
+
+        assert_eq!(vm.memory[vm.mem_ptr], 65);
+    }
+
+    #[test]
+    fn read_at_eof_zeroes_cell_by_default() {
+        let mut vm = VM {
+            input: Box::new(Cursor::new(Vec::new())),
+            ..VM::default()
+        };
+        vm.eval_one(&Instruction::Increment(42)).unwrap();
+        vm.eval_one(&Instruction::Read).unwrap();
+
+        assert_eq!(vm.memory[vm.mem_ptr], 0);
+    }
+
+    #[test]
+    fn read_at_eof_can_be_configured_to_leave_cell_unchanged() {
+        let mut vm = VM {
+            input: Box::new(Cursor::new(Vec::new())),
+            ..VM::default()
+        }
+        .with_eof_policy(EofPolicy::LeaveUnchanged);
+        vm.eval_one(&Instruction::Increment(42)).unwrap();
+        vm.eval_one(&Instruction::Read).unwrap();
+
+        assert_eq!(vm.memory[vm.mem_ptr], 42);
+    }
+
+    #[test]
+    fn cat_program_terminates_on_finite_input() {
+        // `,[.,]`: read a byte, then while it's non-zero print it and read the next one.
+        let bytecode = compile(&[
+            Statement::Read,
+            Statement::JumpIfZero(0),
+            Statement::Print,
+            Statement::Read,
+            Statement::JumpIfNotZero(0),
+        ]);
+
+        let buffer = SharedBuffer::default();
+        let mut vm = VM {
+            input: Box::new(Cursor::new(b"hi".to_vec())),
+            output: Box::new(buffer.clone()),
+            ..VM::default()
+        };
+        vm.eval(&bytecode);
+
+        assert_eq!(buffer.0.borrow().as_slice(), b"hi");
+    }
 }